@@ -1,17 +1,30 @@
+use std::cmp;
 use std::cmp::Ordering::{self, Less, Greater, Equal};
 use std::{u8, u64};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
-use std::ops::{Add, AddAssign};
-use fixed_sizes::BitLength;
-use num::traits::{Zero, One, Unsigned};
+use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, Div, Rem, DivAssign};
+use fixed_sizes::{BitLength, DigitStorage};
+use num::traits::{Zero, One, Unsigned, CheckedAdd, CheckedSub, CheckedMul,
+                  WrappingAdd, WrappingSub, WrappingMul, SaturatingAdd, SaturatingSub};
 
 
-/// A `BigDigit` is a `FixedBigUint`'s composing element.
+/// A `BigDigit` is a `FixedBigUint`'s composing element. `u64` under the
+/// `u64_digit` feature, `u32` otherwise -- a wider digit halves the number
+/// of inner-loop iterations every operation needs, at the cost of losing
+/// the portable `_addcarry_u32`/`_subborrow_u32` path on 32-bit x86.
+#[cfg(not(feature = "u64_digit"))]
 pub type BigDigit = u32;
+#[cfg(feature = "u64_digit")]
+pub type BigDigit = u64;
 
 /// A `DoubleBigDigit` is the internal type used to do the computations.  Its
 /// size is the double of the size of `BigDigit`.
+#[cfg(not(feature = "u64_digit"))]
 pub type DoubleBigDigit = u64;
+#[cfg(feature = "u64_digit")]
+pub type DoubleBigDigit = u128;
 
 pub const ZERO_BIG_DIGIT: BigDigit = 0;
 
@@ -21,10 +34,13 @@ pub mod big_digit {
     use super::DoubleBigDigit;
 
     // `DoubleBigDigit` size dependent
+    #[cfg(not(feature = "u64_digit"))]
     pub const BITS: usize = 32;
+    #[cfg(feature = "u64_digit")]
+    pub const BITS: usize = 64;
 
     pub const BASE: DoubleBigDigit = 1 << BITS;
-    const LO_MASK: DoubleBigDigit = (-1i32 as DoubleBigDigit) >> BITS;
+    const LO_MASK: DoubleBigDigit = BigDigit::max_value() as DoubleBigDigit;
 
     #[inline]
     fn get_hi(n: DoubleBigDigit) -> BigDigit {
@@ -51,9 +67,17 @@ pub mod big_digit {
 // Generic functions for add/subtract/multiply with carry/borrow:
 //
 
-// Add with carry:
+// `adc` gives `carry` full `DoubleBigDigit`-widening semantics: the parameter
+// may be any `BigDigit` value, not just 0/1, because callers
+// that drain a `mac_with_carry` chain (schoolbook multiply, Montgomery REDC,
+// Knuth division) pass forward a carry that can itself be a near-`BigDigit::
+// max_value()` multiply overflow. The x86 `_addcarry_u32`/`_addcarry_u64`
+// instructions only have a single-bit hardware carry flag, so they can't
+// stand in here -- see `adc_bit`/`sbb_bit` below for the narrower case where
+// they do apply. `sbb`'s `borrow` is 0 or 1 only (the only values it ever
+// sets).
 #[inline]
-fn adc(a: BigDigit, b: BigDigit, carry: &mut BigDigit) -> BigDigit {
+pub(crate) fn adc(a: BigDigit, b: BigDigit, carry: &mut BigDigit) -> BigDigit {
     let (hi, lo) = big_digit::from_doublebigdigit((a as DoubleBigDigit) + (b as DoubleBigDigit) +
                                                   (*carry as DoubleBigDigit));
 
@@ -61,9 +85,8 @@ fn adc(a: BigDigit, b: BigDigit, carry: &mut BigDigit) -> BigDigit {
     lo
 }
 
-// Subtract with borrow:
 #[inline]
-fn sbb(a: BigDigit, b: BigDigit, borrow: &mut BigDigit) -> BigDigit {
+pub(crate) fn sbb(a: BigDigit, b: BigDigit, borrow: &mut BigDigit) -> BigDigit {
     let (hi, lo) = big_digit::from_doublebigdigit(big_digit::BASE + (a as DoubleBigDigit) -
                                                   (b as DoubleBigDigit) -
                                                   (*borrow as DoubleBigDigit));
@@ -78,8 +101,73 @@ fn sbb(a: BigDigit, b: BigDigit, borrow: &mut BigDigit) -> BigDigit {
     lo
 }
 
+// On x86/x86_64 the hardware carry flag does this in one instruction via
+// `_addcarry_u32`/`_addcarry_u64`; every other target falls back to plain
+// `adc`/`sbb` above (equally correct here, since the carry/borrow is always
+// 0 or 1 for these callers). Only use `adc_bit`/`sbb_bit` where that's
+// guaranteed -- plain ripple-carry digit addition/subtraction, not anything
+// draining a `mac_with_carry` chain.
+#[cfg(all(target_arch = "x86", not(feature = "u64_digit")))]
+use std::arch::x86::{_addcarry_u32, _subborrow_u32};
+#[cfg(all(target_arch = "x86_64", not(feature = "u64_digit")))]
+use std::arch::x86_64::{_addcarry_u32, _subborrow_u32};
+#[cfg(all(target_arch = "x86_64", feature = "u64_digit"))]
+use std::arch::x86_64::{_addcarry_u64, _subborrow_u64};
+
+// Add with a single-bit carry (0 or 1 only):
+#[cfg(any(all(target_arch = "x86", not(feature = "u64_digit")),
+          all(target_arch = "x86_64", not(feature = "u64_digit"))))]
+#[inline]
+pub(crate) fn adc_bit(a: BigDigit, b: BigDigit, carry: &mut BigDigit) -> BigDigit {
+    let mut out: BigDigit = 0;
+    *carry = _addcarry_u32(*carry as u8, a, b, &mut out) as BigDigit;
+    out
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "u64_digit"))]
+#[inline]
+pub(crate) fn adc_bit(a: BigDigit, b: BigDigit, carry: &mut BigDigit) -> BigDigit {
+    let mut out: BigDigit = 0;
+    *carry = _addcarry_u64(*carry as u8, a, b, &mut out) as BigDigit;
+    out
+}
+
+#[cfg(not(any(all(target_arch = "x86", not(feature = "u64_digit")),
+              all(target_arch = "x86_64", not(feature = "u64_digit")),
+              all(target_arch = "x86_64", feature = "u64_digit"))))]
+#[inline]
+pub(crate) fn adc_bit(a: BigDigit, b: BigDigit, carry: &mut BigDigit) -> BigDigit {
+    adc(a, b, carry)
+}
+
+// Subtract with a single-bit borrow (0 or 1 only):
+#[cfg(any(all(target_arch = "x86", not(feature = "u64_digit")),
+          all(target_arch = "x86_64", not(feature = "u64_digit"))))]
 #[inline]
-fn mac_with_carry(a: BigDigit, b: BigDigit, c: BigDigit, carry: &mut BigDigit) -> BigDigit {
+pub(crate) fn sbb_bit(a: BigDigit, b: BigDigit, borrow: &mut BigDigit) -> BigDigit {
+    let mut out: BigDigit = 0;
+    *borrow = _subborrow_u32(*borrow as u8, a, b, &mut out) as BigDigit;
+    out
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "u64_digit"))]
+#[inline]
+pub(crate) fn sbb_bit(a: BigDigit, b: BigDigit, borrow: &mut BigDigit) -> BigDigit {
+    let mut out: BigDigit = 0;
+    *borrow = _subborrow_u64(*borrow as u8, a, b, &mut out) as BigDigit;
+    out
+}
+
+#[cfg(not(any(all(target_arch = "x86", not(feature = "u64_digit")),
+              all(target_arch = "x86_64", not(feature = "u64_digit")),
+              all(target_arch = "x86_64", feature = "u64_digit"))))]
+#[inline]
+pub(crate) fn sbb_bit(a: BigDigit, b: BigDigit, borrow: &mut BigDigit) -> BigDigit {
+    sbb(a, b, borrow)
+}
+
+#[inline]
+pub(crate) fn mac_with_carry(a: BigDigit, b: BigDigit, c: BigDigit, carry: &mut BigDigit) -> BigDigit {
     let (hi, lo) = big_digit::from_doublebigdigit((a as DoubleBigDigit) +
                                                   (b as DoubleBigDigit) * (c as DoubleBigDigit) +
                                                   (*carry as DoubleBigDigit));
@@ -88,17 +176,38 @@ fn mac_with_carry(a: BigDigit, b: BigDigit, c: BigDigit, carry: &mut BigDigit) -
 }
 
 #[inline]
-fn ones_mask(ones: BigDigit) -> BigDigit {
+pub(crate) fn ones_mask(ones: BigDigit) -> BigDigit {
     let ones_count = ones % (big_digit::BITS as BigDigit);
+
+    // Every `BitLength` in this crate is a whole number of digits wide, so
+    // `ones_count == 0` means the top digit is a complete digit rather than
+    // a partial one with zero significant bits -- leave it unmasked.
+    if ones_count == 0 {
+        return BigDigit::max_value();
+    }
+
     let mut mask = 0 as BigDigit;
 
-    for i in 0..ones_count {
+    for _ in 0..ones_count {
         mask = (mask << 1) | 1;
     }
 
     mask
 }
 
+/// Turns a raw add/mul carry-out into a 0/1 overflow flag relative to a top
+/// digit's `mask`: when `mask` covers the whole digit (no partial top digit)
+/// the carry-out off the array is already that flag, so it's returned as-is;
+/// otherwise any bit set above `mask` signals overflow past the fixed width.
+#[inline]
+fn overflow_flag(carry: BigDigit, mask: BigDigit) -> BigDigit {
+    if mask == BigDigit::max_value() {
+        carry
+    } else {
+        (carry & !mask) >> (big_digit::BITS - mask.leading_zeros() as usize)
+    }
+}
+
 /// Divide a two digit numerator by a one digit divisor, returns quotient and remainder:
 ///
 /// Note: the caller must ensure that both the quotient and remainder will fit into a single digit.
@@ -114,45 +223,97 @@ fn div_wide(hi: BigDigit, lo: BigDigit, divisor: BigDigit) -> (BigDigit, BigDigi
     ((lhs / rhs) as BigDigit, (lhs % rhs) as BigDigit)
 }
 
-#[derive(Clone, Debug, Hash)]
-pub struct FixedBigUint<B> {
-    data: Vec<BigDigit>,
+/// `FixedBigUint`'s digit storage lives inline, in the array `B::Storage`
+/// fixes at the type level (see `BitLength`) — no heap allocation, unlike a
+/// plain `Vec<BigDigit>`.
+pub struct FixedBigUint<B: BitLength> {
+    data: B::Storage,
     mask: BigDigit,
     size: PhantomData<B>,
 }
 
+impl<B: BitLength> Clone for FixedBigUint<B> {
+    #[inline]
+    fn clone(&self) -> FixedBigUint<B> {
+        FixedBigUint {
+            data: self.data.clone(),
+            mask: self.mask,
+            size: PhantomData,
+        }
+    }
+}
+
+impl<B: BitLength> fmt::Debug for FixedBigUint<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FixedBigUint")
+            .field("data", &self.data.as_ref())
+            .field("mask", &self.mask)
+            .finish()
+    }
+}
+
+impl<B: BitLength> Hash for FixedBigUint<B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.as_ref().hash(state);
+        self.mask.hash(state);
+    }
+}
+
 impl<B> FixedBigUint<B> where B: BitLength {
     /// Creates and initializes a `BigUint`.
     ///
-    /// The digits are in little-endian base 2^32. If vector is too long, the 
+    /// The digits are in little-endian base 2^`big_digit::BITS`. If vector is too long, the
     /// most significant digits will be truncated.
     #[inline]
     fn new(mut digits: Vec<BigDigit>) -> FixedBigUint<B> {
-        let div = B::bit_len() / big_digit::BITS; 
-        let rem = B::bit_len() % big_digit::BITS;
-        let digit_len = if rem == 0 { div } else { div+1 };
-        let mask  = ones_mask(rem as BigDigit);
+        let digit_len = B::Storage::zeroed().as_ref().len();
 
-        if digits.len() > digit_len {
-            // If the vector is too long, truncate digits.
-            digits.truncate(digit_len);
-        }
+        // Pad or truncate digits to the digit length.
+        digits.resize(digit_len, 0);
 
-        // Pad digits to the digit length.
-        for i in 0..(digit_len - digits.len()) {
-            digits.push(0);
-        }
+        let mut storage = B::Storage::zeroed();
+        storage.as_mut().copy_from_slice(&digits[..]);
+
+        FixedBigUint::from_storage(storage)
+    }
 
-        // digits is not empty here.
-        let msd = digits.pop().unwrap() & mask;
-        digits.push(msd);
+    /// Wraps an already-filled `B::Storage` directly, with no intermediate
+    /// `Vec` -- this is what `__add`/`__sub`/`__mul` build their result
+    /// through, since they always produce exactly `B::Storage`'s digit count
+    /// and never need `new`'s pad-or-truncate step.
+    #[inline]
+    fn from_storage(mut storage: B::Storage) -> FixedBigUint<B> {
+        let digit_len = storage.as_ref().len();
+        let rem = B::bit_len() % big_digit::BITS;
+        let mask = ones_mask(rem as BigDigit);
+
+        storage.as_mut()[digit_len - 1] &= mask;
 
         FixedBigUint {
-            data: digits,
+            data: storage,
             mask: mask,
             size: PhantomData,
         }
     }
+
+    /// Builds a `FixedBigUint` from a raw little-endian digit vector, padding
+    /// or truncating to the type's fixed width exactly like `new`.
+    #[inline]
+    pub(crate) fn from_digits(digits: Vec<BigDigit>) -> FixedBigUint<B> {
+        FixedBigUint::new(digits)
+    }
+
+    /// The little-endian digits backing this value.
+    #[inline]
+    pub(crate) fn digits(&self) -> &[BigDigit] {
+        self.data.as_ref()
+    }
+
+    /// The fixed number of `BigDigit`s this type is stored in.
+    #[inline]
+    pub(crate) fn digit_len(&self) -> usize {
+        self.data.as_ref().len()
+    }
 }
 
 impl<B> PartialEq for FixedBigUint<B> where B: BitLength {
@@ -174,10 +335,11 @@ impl<B> PartialOrd for FixedBigUint<B> where B: BitLength {
     }
 }
 
-fn cmp_slice(a: &[BigDigit], b: &[BigDigit]) -> Ordering {
-    debug_assert!(a.last() != Some(&0));
-    debug_assert!(b.last() != Some(&0));
-
+// Note: unlike a trimmed bignum representation, `a` and `b` here are
+// routinely full fixed-width digit slices with zeroed-out leading digits
+// (e.g. comparing two small values of a wide `FixedBigUint`), so no
+// "last digit is nonzero" invariant holds to assert on.
+pub(crate) fn cmp_slice(a: &[BigDigit], b: &[BigDigit]) -> Ordering {
     let (a_len, b_len) = (a.len(), b.len());
     if a_len < b_len {
         return Less;
@@ -200,10 +362,9 @@ fn cmp_slice(a: &[BigDigit], b: &[BigDigit]) -> Ordering {
 impl<B> Ord for FixedBigUint<B> where B: BitLength {
     #[inline]
     fn cmp(&self, other: &FixedBigUint<B>) -> Ordering {
-        assert_eq!(self.data.len(), other.data.len());
         assert_eq!(self.size, other.size);
 
-        cmp_slice(&self.data[..], &other.data[..])
+        cmp_slice(self.data.as_ref(), other.data.as_ref())
     }
 }
 
@@ -222,7 +383,7 @@ impl<B> Zero for FixedBigUint<B> where B: BitLength {
 
     #[inline]
     fn is_zero(&self) -> bool {
-        for term in self.data.iter() {
+        for term in self.data.as_ref().iter() {
             if *term != 0 {
                 return false;
             }
@@ -231,19 +392,13 @@ impl<B> Zero for FixedBigUint<B> where B: BitLength {
         true
     }
 }
-/*
 impl<B> One for FixedBigUint<B> where B: BitLength {
     #[inline]
     fn one() -> FixedBigUint<B> {
-        let mut uint = Zero::zero();
-
-        uint.data.pop();
-        uint.data.push(1 as BigDigit);
-
-        uint
+        FixedBigUint::<B>::new(vec![1])
     }
 }
-*/
+
 //impl<B> Unsigned for FixedBigUint<B> where B: BitLength {}
 
 //forward_all_binop_to_val_ref_commutative!(impl Add for BigUint, add);
@@ -258,16 +413,10 @@ fn __add_assign(a: &mut [BigDigit], b: &[BigDigit], mask: BigDigit) -> BigDigit
     let mut carry = 0;
 
     for (ai, bi) in a.iter_mut().zip(b.iter()) {
-        if carry != 0 {
-            *ai = adc(*ai, 0, &mut carry);
-        }
-
-        *ai += adc(*ai, *bi, &mut carry);
+        *ai = adc_bit(*ai, *bi, &mut carry);
     }
-    
-    carry = (carry & !mask) >> (big_digit::BITS - mask.leading_zeros() as usize);
 
-    carry
+    overflow_flag(carry, mask)
 }
 
 // Only for the Add impl:
@@ -277,21 +426,15 @@ fn __add_assign(a: &mut [BigDigit], b: &[BigDigit], mask: BigDigit) -> BigDigit
 /// The caller _must_ ensure that a and b are identical in length. Typically this is ensured by
 /// using this function to implement addition for fixed precision arithmetic. 
 fn __add<B: BitLength>(a: &[BigDigit], b: &[BigDigit]) -> (FixedBigUint<B>, BigDigit) {
+    let mut storage = B::Storage::zeroed();
     let mut carry = 0;
-    let mut cvec = vec![];
 
-    for (ai, bi) in a.iter().zip(b.iter()) {
-        let mut ci = 0; 
-        if carry != 0 {
-            ci = adc(*ai, 0, &mut carry);
-        }
-
-        ci += adc(*ai, *bi, &mut carry);
-        cvec.push(ci);
+    for ((ci, ai), bi) in storage.as_mut().iter_mut().zip(a.iter()).zip(b.iter()) {
+        *ci = adc_bit(*ai, *bi, &mut carry);
     }
 
-    let c = FixedBigUint::new(cvec);
-    carry = (carry & !c.mask) >> (big_digit::BITS - c.mask.leading_zeros() as usize);
+    let c = FixedBigUint::from_storage(storage);
+    let carry = overflow_flag(carry, c.mask);
 
     (c, carry)
 }
@@ -301,7 +444,7 @@ impl<B> Add<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
 
     #[allow(unused_variables)]
     fn add(self, other: FixedBigUint<B>) -> FixedBigUint<B> {
-        let (result, carry) = __add(&self.data[..], &other.data[..]);
+        let (result, carry) = __add(self.data.as_ref(), other.data.as_ref());
 
         result
     }
@@ -312,7 +455,7 @@ impl<'a, B> Add<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
 
     #[allow(unused_variables)]
     fn add(self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
-        let (result, carry) = __add(&self.data[..], &other.data[..]);
+        let (result, carry) = __add(self.data.as_ref(), other.data.as_ref());
 
         result
     }
@@ -322,7 +465,7 @@ impl<'a, B> AddAssign<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLengt
 
     #[allow(unused_variables)]
     fn add_assign(&mut self, other: &'a FixedBigUint<B>) {
-        let carry = __add_assign(&mut self.data[..], &other.data[..], self.mask);
+        let carry = __add_assign(self.data.as_mut(), other.data.as_ref(), self.mask);
     }
 }
 
@@ -330,6 +473,602 @@ impl<B> AddAssign<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
 
     #[allow(unused_variables)]
     fn add_assign(&mut self, other: FixedBigUint<B>) {
-        let carry = __add_assign(&mut self.data[..], &other.data[..], self.mask);
+        let carry = __add_assign(self.data.as_mut(), other.data.as_ref(), self.mask);
+    }
+}
+
+// Only for the SubAssign impl:
+/// a -= b
+///
+/// Two's-complement wraparound: if `b > a` the result is `a - b mod
+/// 2^bit_len`, and the returned borrow is `1`. The caller _must_ ensure that
+/// a and b are identical in length.
+fn __sub_assign(a: &mut [BigDigit], b: &[BigDigit], mask: BigDigit) -> BigDigit {
+    let mut borrow = 0;
+
+    for (ai, bi) in a.iter_mut().zip(b.iter()) {
+        *ai = sbb_bit(*ai, *bi, &mut borrow);
+    }
+
+    let msd = a.len() - 1;
+    a[msd] &= mask;
+
+    borrow
+}
+
+// Only for the Sub impl:
+/// c = a - b
+///
+/// Two's-complement wraparound: if `b > a` the result is `a - b mod
+/// 2^bit_len`, and the returned borrow is `1`. The caller _must_ ensure that
+/// a and b are identical in length.
+fn __sub<B: BitLength>(a: &[BigDigit], b: &[BigDigit]) -> (FixedBigUint<B>, BigDigit) {
+    let mut storage = B::Storage::zeroed();
+    let mut borrow = 0;
+
+    for ((ci, ai), bi) in storage.as_mut().iter_mut().zip(a.iter()).zip(b.iter()) {
+        *ci = sbb_bit(*ai, *bi, &mut borrow);
+    }
+
+    (FixedBigUint::from_storage(storage), borrow)
+}
+
+impl<B> Sub<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    #[allow(unused_variables)]
+    fn sub(self, other: FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, borrow) = __sub(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+impl<'a, B> Sub<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    #[allow(unused_variables)]
+    fn sub(self, other: &'a FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, borrow) = __sub(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+impl<'a, B> SubAssign<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+
+    #[allow(unused_variables)]
+    fn sub_assign(&mut self, other: &'a FixedBigUint<B>) {
+        let borrow = __sub_assign(self.data.as_mut(), other.data.as_ref(), self.mask);
+    }
+}
+
+impl<B> SubAssign<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+
+    #[allow(unused_variables)]
+    fn sub_assign(&mut self, other: FixedBigUint<B>) {
+        let borrow = __sub_assign(self.data.as_mut(), other.data.as_ref(), self.mask);
+    }
+}
+
+// At or above this width (in digits) a schoolbook multiply does too much quadratic
+// work; Bits2048 and up switch to Karatsuba. Expressed in digits rather than
+// bits, so it stays at the same bit width whether a digit is 32 or 64 bits.
+const KARATSUBA_DIGIT_THRESHOLD: usize = 2048 / big_digit::BITS;
+
+/// dst += src, with `src` word-shifted `shift` digits into `dst`. Any carry that walks
+/// off the end of `dst` is dropped, matching the truncating semantics the
+/// rest of the fixed-width arithmetic uses.
+fn __add_shifted_in_place(dst: &mut [BigDigit], src: &[BigDigit], shift: usize) {
+    let mut carry: BigDigit = 0;
+
+    for (i, &si) in src.iter().enumerate() {
+        if shift + i >= dst.len() {
+            break;
+        }
+        dst[shift + i] = adc(dst[shift + i], si, &mut carry);
+    }
+
+    let mut k = shift + src.len();
+    while carry != 0 && k < dst.len() {
+        dst[k] = adc(dst[k], 0, &mut carry);
+        k += 1;
+    }
+}
+
+/// Returns a + b, where a and b may have different lengths; the result is one digit
+/// longer than the longer operand so the final carry always has somewhere to
+/// go.
+fn __add_digits(a: &[BigDigit], b: &[BigDigit]) -> Vec<BigDigit> {
+    let len = cmp::max(a.len(), b.len());
+    let mut out = Vec::with_capacity(len + 1);
+    let mut carry: BigDigit = 0;
+
+    for i in 0..len {
+        let ai = a.get(i).cloned().unwrap_or(0);
+        let bi = b.get(i).cloned().unwrap_or(0);
+        out.push(adc(ai, bi, &mut carry));
+    }
+    out.push(carry);
+
+    out
+}
+
+/// a -= b in place, assuming a >= b. Digits of `a` beyond `b`'s length simply
+/// absorb the remaining borrow.
+fn __sub_digits_in_place(a: &mut [BigDigit], b: &[BigDigit]) {
+    let mut borrow: BigDigit = 0;
+
+    for (i, ai) in a.iter_mut().enumerate() {
+        let bi = b.get(i).cloned().unwrap_or(0);
+        *ai = sbb(*ai, bi, &mut borrow);
+    }
+}
+
+/// Schoolbook multiply: acc += a * b, where acc is at least `a.len() + b.len()`
+/// digits long.
+fn __mul_schoolbook(a: &[BigDigit], b: &[BigDigit], acc: &mut [BigDigit]) {
+    for (i, &ai) in a.iter().enumerate() {
+        if ai == 0 {
+            continue;
+        }
+
+        let mut carry = 0;
+        for (j, &bj) in b.iter().enumerate() {
+            acc[i + j] = mac_with_carry(acc[i + j], ai, bj, &mut carry);
+        }
+
+        let mut k = i + b.len();
+        while carry != 0 {
+            acc[k] = adc(acc[k], 0, &mut carry);
+            k += 1;
+        }
+    }
+}
+
+fn __mul_to_vec(a: &[BigDigit], b: &[BigDigit]) -> Vec<BigDigit> {
+    let mut acc = vec![0 as BigDigit; a.len() + b.len()];
+    __mul_schoolbook(a, b, &mut acc);
+    acc
+}
+
+/// Karatsuba multiply: acc = a * b, splitting each operand into high/low
+/// halves at `digit_len / 2` and recombining `z2<<2h + z1<<h + z0`.
+fn __mul_karatsuba(a: &[BigDigit], b: &[BigDigit], acc: &mut [BigDigit]) {
+    let half = a.len() / 2;
+
+    let (a_lo, a_hi) = a.split_at(half);
+    let (b_lo, b_hi) = b.split_at(half);
+
+    let z0 = __mul_to_vec(a_lo, b_lo);
+    let z2 = __mul_to_vec(a_hi, b_hi);
+
+    let a_sum = __add_digits(a_lo, a_hi);
+    let b_sum = __add_digits(b_lo, b_hi);
+
+    let mut z1 = __mul_to_vec(&a_sum, &b_sum);
+    __sub_digits_in_place(&mut z1, &z0);
+    __sub_digits_in_place(&mut z1, &z2);
+
+    __add_shifted_in_place(acc, &z0, 0);
+    __add_shifted_in_place(acc, &z1, half);
+    __add_shifted_in_place(acc, &z2, 2 * half);
+}
+
+/// a * b, kept at the full `2 * a.len()` digits (no truncation to the fixed
+/// width). Montgomery multiplication in `mod_pow` needs the untruncated
+/// product to reduce itself, rather than the modular one `Mul` produces.
+pub(crate) fn wide_mul(a: &[BigDigit], b: &[BigDigit]) -> Vec<BigDigit> {
+    let mut full = vec![0 as BigDigit; a.len() + b.len()];
+
+    if a.len() >= KARATSUBA_DIGIT_THRESHOLD {
+        __mul_karatsuba(a, b, &mut full);
+    } else {
+        __mul_schoolbook(a, b, &mut full);
+    }
+
+    full
+}
+
+// Only for the Mul impl:
+/// c = a * b
+///
+/// The product is computed over twice the digit length and then truncated
+/// (like `FixedBigUint::new` already does for any too-long digit vector) down
+/// to `a.len()` digits. The caller _must_ ensure that a and b are identical
+/// in length.
+fn __mul<B: BitLength>(a: &[BigDigit], b: &[BigDigit]) -> (FixedBigUint<B>, BigDigit) {
+    let digit_len = a.len();
+    let full = wide_mul(a, b);
+
+    let rem = B::bit_len() % big_digit::BITS;
+    let mask = ones_mask(rem as BigDigit);
+    let overflowed = full[digit_len..].iter().any(|&d| d != 0) ||
+                     (full[digit_len - 1] & !mask) != 0;
+
+    let mut storage = B::Storage::zeroed();
+    storage.as_mut().copy_from_slice(&full[..digit_len]);
+    let carry = if overflowed { 1 } else { 0 };
+
+    (FixedBigUint::from_storage(storage), carry)
+}
+
+impl<B> Mul<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    #[allow(unused_variables)]
+    fn mul(self, other: FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, carry) = __mul(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+impl<'a, B> Mul<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    #[allow(unused_variables)]
+    fn mul(self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, carry) = __mul(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+/// All-ones up to `B::bit_len()`, i.e. `2^bit_len - 1`. Goes through
+/// `FixedBigUint::new` so the usual top-digit masking truncates it to width.
+fn __max_value<B: BitLength>() -> FixedBigUint<B> {
+    FixedBigUint::new(vec![BigDigit::max_value(); B::Storage::zeroed().as_ref().len()])
+}
+
+impl<B> CheckedAdd for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn checked_add(&self, other: &FixedBigUint<B>) -> Option<FixedBigUint<B>> {
+        let (result, carry) = __add(self.data.as_ref(), other.data.as_ref());
+
+        if carry != 0 { None } else { Some(result) }
+    }
+}
+
+impl<B> CheckedSub for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn checked_sub(&self, other: &FixedBigUint<B>) -> Option<FixedBigUint<B>> {
+        let (result, borrow) = __sub(self.data.as_ref(), other.data.as_ref());
+
+        if borrow != 0 { None } else { Some(result) }
+    }
+}
+
+impl<B> CheckedMul for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn checked_mul(&self, other: &FixedBigUint<B>) -> Option<FixedBigUint<B>> {
+        let (result, overflow) = __mul(self.data.as_ref(), other.data.as_ref());
+
+        if overflow != 0 { None } else { Some(result) }
+    }
+}
+
+impl<B> WrappingAdd for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn wrapping_add(&self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, _carry) = __add(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+impl<B> WrappingSub for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn wrapping_sub(&self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, _borrow) = __sub(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+impl<B> WrappingMul for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn wrapping_mul(&self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, _overflow) = __mul(self.data.as_ref(), other.data.as_ref());
+
+        result
+    }
+}
+
+impl<B> SaturatingAdd for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn saturating_add(&self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, carry) = __add(self.data.as_ref(), other.data.as_ref());
+
+        if carry != 0 { __max_value::<B>() } else { result }
+    }
+}
+
+impl<B> SaturatingSub for FixedBigUint<B> where B: BitLength {
+    #[inline]
+    fn saturating_sub(&self, other: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let (result, borrow) = __sub(self.data.as_ref(), other.data.as_ref());
+
+        if borrow != 0 { Zero::zero() } else { result }
+    }
+}
+
+/// Shifts `x` left by `shift` bits in place (`0 <= shift < big_digit::BITS`), returning
+/// whatever bits fall off the top digit.
+fn shl_bits(x: &mut [BigDigit], shift: usize) -> BigDigit {
+    if shift == 0 {
+        return 0;
+    }
+
+    let mut carry = 0;
+    for xi in x.iter_mut() {
+        let next_carry = *xi >> (big_digit::BITS - shift);
+        *xi = (*xi << shift) | carry;
+        carry = next_carry;
+    }
+
+    carry
+}
+
+/// Shifts `x` right by `shift` bits in place (`0 <= shift < big_digit::BITS`), shifting
+/// `carry_in`'s low `shift` bits into the vacated top bits.
+fn shr_bits(x: &mut [BigDigit], shift: usize, carry_in: BigDigit) {
+    if shift == 0 {
+        return;
+    }
+
+    let mut carry = carry_in << (big_digit::BITS - shift);
+    for xi in x.iter_mut().rev() {
+        let next_carry = *xi << (big_digit::BITS - shift);
+        *xi = (*xi >> shift) | carry;
+        carry = next_carry;
+    }
+}
+
+impl<B> FixedBigUint<B> where B: BitLength {
+    /// Divides `self` by `other`, returning `(quotient, remainder)`.
+    ///
+    /// Implements Knuth's Algorithm D (TAOCP vol. 2, section 4.3.1): the
+    /// single-digit-divisor case goes through `div_wide` directly, otherwise
+    /// both operands are normalized (left-shifted so the divisor's top digit
+    /// has its high bit set), each quotient digit is estimated from the top
+    /// two digits of the running remainder and corrected down, then
+    /// `qhat * divisor` is subtracted from the remainder's digit window,
+    /// adding the divisor back once if the estimate was still one too high.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn div_rem(&self, other: &FixedBigUint<B>) -> (FixedBigUint<B>, FixedBigUint<B>) {
+        assert!(!other.is_zero(), "division by zero");
+
+        let mut v = other.data.as_ref().to_vec();
+        while v.len() > 1 && *v.last().unwrap() == 0 {
+            v.pop();
+        }
+
+        if v.len() == 1 {
+            let divisor = v[0];
+            let mut quotient = vec![0 as BigDigit; self.data.as_ref().len()];
+            let mut rem: BigDigit = 0;
+
+            for i in (0..self.data.as_ref().len()).rev() {
+                let (q, r) = div_wide(rem, self.data.as_ref()[i], divisor);
+                quotient[i] = q;
+                rem = r;
+            }
+
+            return (FixedBigUint::new(quotient), FixedBigUint::new(vec![rem]));
+        }
+
+        let mut u = self.data.as_ref().to_vec();
+        while u.len() > 1 && *u.last().unwrap() == 0 {
+            u.pop();
+        }
+
+        if cmp_slice(&u, &v) == Less {
+            return (Zero::zero(), self.clone());
+        }
+
+        let n = v.len();
+        let m = u.len() - n;
+        let shift = v[n - 1].leading_zeros() as usize;
+
+        let mut vn = v;
+        shl_bits(&mut vn, shift);
+
+        let mut un = vec![0 as BigDigit; u.len() + 1];
+        un[..u.len()].copy_from_slice(&u);
+        shl_bits(&mut un, shift);
+
+        let mut quotient = vec![0 as BigDigit; m + 1];
+
+        for j in (0..=m).rev() {
+            // D3: estimate qhat from the top two digits of the window.
+            let top2 = big_digit::to_doublebigdigit(un[j + n], un[j + n - 1]);
+            let mut qhat = top2 / (vn[n - 1] as DoubleBigDigit);
+            let mut rhat = top2 % (vn[n - 1] as DoubleBigDigit);
+
+            if qhat >= big_digit::BASE {
+                qhat = big_digit::BASE - 1;
+                rhat = top2 - qhat * (vn[n - 1] as DoubleBigDigit);
+            }
+
+            while rhat < big_digit::BASE &&
+                  qhat * (vn[n - 2] as DoubleBigDigit) >
+                  rhat * big_digit::BASE + (un[j + n - 2] as DoubleBigDigit) {
+                qhat -= 1;
+                rhat += vn[n - 1] as DoubleBigDigit;
+            }
+
+            // D4: multiply qhat*vn and subtract it from the window un[j..=j+n].
+            let qhat_digit = qhat as BigDigit;
+            let mut mul_carry: BigDigit = 0;
+            let mut prod = vec![0 as BigDigit; n + 1];
+            for i in 0..n {
+                prod[i] = mac_with_carry(0, qhat_digit, vn[i], &mut mul_carry);
+            }
+            prod[n] = mul_carry;
+
+            let mut borrow: BigDigit = 0;
+            for i in 0..=n {
+                un[j + i] = sbb(un[j + i], prod[i], &mut borrow);
+            }
+
+            // D5/D6: the subtraction went negative, so qhat was one too big;
+            // add a copy of vn back in and step the estimate down.
+            if borrow != 0 {
+                qhat -= 1;
+                let mut carry: BigDigit = 0;
+                for i in 0..n {
+                    un[j + i] = adc(un[j + i], vn[i], &mut carry);
+                }
+                un[j + n] = adc(un[j + n], 0, &mut carry);
+            }
+
+            quotient[j] = qhat as BigDigit;
+        }
+
+        // D8: denormalize the remainder.
+        let mut remainder = un[..n].to_vec();
+        shr_bits(&mut remainder, shift, 0);
+
+        (FixedBigUint::new(quotient), FixedBigUint::new(remainder))
+    }
+}
+
+impl<B> Div<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    fn div(self, other: FixedBigUint<B>) -> FixedBigUint<B> {
+        self.div_rem(&other).0
+    }
+}
+
+impl<'a, B> Div<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    fn div(self, other: &'a FixedBigUint<B>) -> FixedBigUint<B> {
+        self.div_rem(other).0
+    }
+}
+
+impl<B> Rem<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    fn rem(self, other: FixedBigUint<B>) -> FixedBigUint<B> {
+        self.div_rem(&other).1
+    }
+}
+
+impl<'a, B> Rem<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    type Output = FixedBigUint<B>;
+
+    fn rem(self, other: &'a FixedBigUint<B>) -> FixedBigUint<B> {
+        self.div_rem(other).1
+    }
+}
+
+impl<B> DivAssign<FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    fn div_assign(&mut self, other: FixedBigUint<B>) {
+        *self = self.div_rem(&other).0;
+    }
+}
+
+impl<'a, B> DivAssign<&'a FixedBigUint<B>> for FixedBigUint<B> where B: BitLength {
+    fn div_assign(&mut self, other: &'a FixedBigUint<B>) {
+        *self = self.div_rem(other).0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixed_sizes::{Bits128, Bits2048};
+
+    fn mk128(digits: Vec<BigDigit>) -> FixedBigUint<Bits128> {
+        FixedBigUint::from_digits(digits)
+    }
+
+    fn split(x: DoubleBigDigit) -> (BigDigit, BigDigit) {
+        big_digit::from_doublebigdigit(x)
+    }
+
+    #[test]
+    fn mul_schoolbook_basic() {
+        let a = mk128(vec![123456789]);
+        let b = mk128(vec![987654321]);
+        let (hi, lo) = split(123456789 as DoubleBigDigit * 987654321 as DoubleBigDigit);
+        assert_eq!(a * b, mk128(vec![lo, hi]));
+    }
+
+    #[test]
+    fn mul_karatsuba_basic() {
+        // Bits2048 is at KARATSUBA_DIGIT_THRESHOLD, so `*` here exercises
+        // __mul_karatsuba; the expected value is computed independently of
+        // both, so this also guards against the Karatsuba and schoolbook
+        // paths silently agreeing on a shared bug.
+        let a = FixedBigUint::<Bits2048>::from_digits(vec![123456789]);
+        let b = FixedBigUint::<Bits2048>::from_digits(vec![987654321]);
+        let (hi, lo) = split(123456789 as DoubleBigDigit * 987654321 as DoubleBigDigit);
+        assert_eq!(a * b, FixedBigUint::<Bits2048>::from_digits(vec![lo, hi]));
+    }
+
+    #[test]
+    fn div_rem_single_digit_divisor() {
+        let a = mk128(vec![100]);
+        let b = mk128(vec![7]);
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q, mk128(vec![14]));
+        assert_eq!(r, mk128(vec![2]));
+    }
+
+    #[test]
+    fn div_rem_multi_digit_divisor() {
+        // Both operands span more than one digit, so this exercises Knuth's
+        // normalize/estimate/correct path rather than the single-digit
+        // div_wide shortcut.
+        let a = mk128(vec![0, 5]);
+        let b = mk128(vec![0, 1]);
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q, mk128(vec![5]));
+        assert_eq!(r, mk128(vec![0, 0]));
+    }
+
+    #[test]
+    fn checked_add_overflow() {
+        let max: FixedBigUint<Bits128> = __max_value();
+        let one = mk128(vec![1]);
+        assert_eq!(max.checked_add(&one), None);
+        assert_eq!(max.wrapping_add(&one), mk128(vec![0, 0, 0, 0]));
+        assert_eq!(max.saturating_add(&one), max);
+    }
+
+    #[test]
+    fn checked_sub_underflow() {
+        let zero: FixedBigUint<Bits128> = Zero::zero();
+        let one = mk128(vec![1]);
+        let max: FixedBigUint<Bits128> = __max_value();
+        assert_eq!(zero.checked_sub(&one), None);
+        assert_eq!(zero.wrapping_sub(&one), max);
+        assert_eq!(zero.saturating_sub(&one), zero);
+    }
+
+    #[test]
+    fn checked_mul_overflow() {
+        // `half_max` is 2^127 (the top bit of Bits128's most significant
+        // digit); `* 4` pushes it to 2^129, past the 128-bit width, so the
+        // exact product wraps all the way around to zero. Built from
+        // big_digit::BITS and the type's actual digit length rather than a
+        // hardcoded 4-element vector, so this holds under both the default
+        // 32-bit BigDigit and the u64_digit feature's 64-bit one.
+        let digit_len = FixedBigUint::<Bits128>::zero().digits().len();
+        let mut digits = vec![0 as BigDigit; digit_len];
+        digits[digit_len - 1] = 1 << (big_digit::BITS - 1);
+        let half_max = mk128(digits);
+
+        let four = mk128(vec![4]);
+        assert_eq!(half_max.checked_mul(&four), None);
+        assert_eq!(half_max.wrapping_mul(&four), mk128(vec![0 as BigDigit; digit_len]));
     }
 }
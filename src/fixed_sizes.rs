@@ -1,10 +1,51 @@
+use fixed_bigint::{BigDigit, big_digit};
+
+/// A fixed-size, stack-allocated home for a `FixedBigUint`'s digits.
+///
+/// Plain `[BigDigit; N]` arrays only get a blanket `Default` impl for small
+/// `N`, so this supplies the zero value directly for every digit count this
+/// crate uses instead of relying on that.
+pub trait DigitStorage: Clone + AsRef<[BigDigit]> + AsMut<[BigDigit]> {
+    fn zeroed() -> Self;
+}
+
+macro_rules! impl_digit_storage {
+    ($n:expr) => {
+        impl DigitStorage for [BigDigit; $n] {
+            #[inline]
+            fn zeroed() -> Self { [0; $n] }
+        }
+    };
+}
+
+// Every digit count a `BitLength` below can resolve to, across both the
+// 32-bit (`big_digit::BITS == 32`) and `u64_digit` (`== 64`) digit widths.
+impl_digit_storage!(2);
+impl_digit_storage!(4);
+impl_digit_storage!(6);
+impl_digit_storage!(8);
+impl_digit_storage!(12);
+impl_digit_storage!(16);
+impl_digit_storage!(24);
+impl_digit_storage!(32);
+impl_digit_storage!(64);
+impl_digit_storage!(128);
+impl_digit_storage!(256);
+impl_digit_storage!(512);
+
 pub trait BitLength {
+    /// Inline digit storage sized exactly to this type's bit width, so a
+    /// `FixedBigUint` of this size lives entirely on the stack.
+    type Storage: DigitStorage;
+
     fn bit_len() -> usize;
 }
 
 #[derive(PartialEq, Eq)]
 pub struct Bits128 {}
 impl BitLength for Bits128 {
+    type Storage = [BigDigit; 128 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 128 }
 }
@@ -12,13 +53,17 @@ impl BitLength for Bits128 {
 #[derive(PartialEq, Eq)]
 pub struct Bits256 {}
 impl BitLength for Bits256 {
+    type Storage = [BigDigit; 256 / big_digit::BITS];
+
     #[inline]
-    fn bit_len() -> usize { 256 }   
+    fn bit_len() -> usize { 256 }
 }
 
 #[derive(PartialEq, Eq)]
 pub struct Bits384 {}
 impl BitLength for Bits384 {
+    type Storage = [BigDigit; 384 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 384 }
 }
@@ -26,13 +71,17 @@ impl BitLength for Bits384 {
 #[derive(PartialEq, Eq)]
 pub struct Bits512 {}
 impl BitLength for Bits512 {
-    #[inline]    
+    type Storage = [BigDigit; 512 / big_digit::BITS];
+
+    #[inline]
     fn bit_len() -> usize { 512 }
 }
 
 #[derive(PartialEq, Eq)]
 pub struct Bits768 {}
 impl BitLength for Bits768 {
+    type Storage = [BigDigit; 768 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 768 }
 }
@@ -40,6 +89,8 @@ impl BitLength for Bits768 {
 #[derive(PartialEq, Eq)]
 pub struct Bits1024 {}
 impl BitLength for Bits1024 {
+    type Storage = [BigDigit; 1024 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 1024 }
 }
@@ -47,6 +98,8 @@ impl BitLength for Bits1024 {
 #[derive(PartialEq, Eq)]
 pub struct Bits2048 {}
 impl BitLength for Bits2048 {
+    type Storage = [BigDigit; 2048 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 2048 }
 }
@@ -54,6 +107,8 @@ impl BitLength for Bits2048 {
 #[derive(PartialEq, Eq)]
 pub struct Bits4096 {}
 impl BitLength for Bits4096 {
+    type Storage = [BigDigit; 4096 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 4096 }
 }
@@ -61,6 +116,8 @@ impl BitLength for Bits4096 {
 #[derive(PartialEq, Eq)]
 pub struct Bits8192 {}
 impl BitLength for Bits8192 {
+    type Storage = [BigDigit; 8192 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 8192 }
 }
@@ -68,6 +125,8 @@ impl BitLength for Bits8192 {
 #[derive(PartialEq, Eq)]
 pub struct Bits16384 {}
 impl BitLength for Bits16384 {
+    type Storage = [BigDigit; 16384 / big_digit::BITS];
+
     #[inline]
     fn bit_len() -> usize { 16384 }
 }
@@ -1,5 +1,186 @@
+use fixed_bigint::{FixedBigUint, BigDigit, big_digit, adc, sbb, mac_with_carry, cmp_slice, wide_mul};
+use fixed_sizes::BitLength;
+use std::cmp::Ordering::Less;
+
 pub trait ModPow {
     type IntType;
 
     fn mod_pow(base: Self::IntType, exponent: Self::IntType, modulus: Self::IntType) -> Self::IntType;
-}
\ No newline at end of file
+}
+
+/// Montgomery reduction context for a fixed, odd modulus `n`.
+///
+/// `R` is implicitly `2^(BITS*k)` where `k` is `n`'s digit length and `BITS`
+/// is `big_digit::BITS`. `n0inv` is the REDC constant `n' = -n^{-1} mod
+/// 2^BITS`, computed once per `mod_pow` call and reused for every Montgomery
+/// multiplication in the exponentiation.
+struct Montgomery<'a, B> where B: BitLength + 'a {
+    modulus: &'a FixedBigUint<B>,
+    n0inv: BigDigit,
+    digit_len: usize,
+}
+
+impl<'a, B> Montgomery<'a, B> where B: BitLength + 'a {
+    fn new(modulus: &'a FixedBigUint<B>) -> Montgomery<'a, B> {
+        let n0 = modulus.digits()[0];
+        assert_eq!(n0 & 1, 1, "ModPow::mod_pow requires an odd modulus");
+
+        // Word inverse of n0 mod 2^BITS via Newton's method: each iteration
+        // doubles the number of correct low bits, so log2(BITS) rounds take
+        // it from 1 bit to all of them (5 rounds for a 32-bit digit, 6 for a
+        // 64-bit one).
+        let mut inv: BigDigit = 1;
+        for _ in 0..(big_digit::BITS as u32).trailing_zeros() {
+            inv = inv.wrapping_mul((2 as BigDigit).wrapping_sub(n0.wrapping_mul(inv)));
+        }
+
+        Montgomery {
+            modulus: modulus,
+            n0inv: inv.wrapping_neg(),
+            digit_len: modulus.digit_len(),
+        }
+    }
+
+    /// REDC: given `t` of up to `2*digit_len` digits, returns `t / R mod n`.
+    fn redc(&self, mut t: Vec<BigDigit>) -> FixedBigUint<B> {
+        let k = self.digit_len;
+        let n = self.modulus.digits();
+        t.resize(2 * k + 1, 0);
+
+        for i in 0..k {
+            let m = t[i].wrapping_mul(self.n0inv);
+            let mut carry = 0;
+            for j in 0..k {
+                t[i + j] = mac_with_carry(t[i + j], m, n[j], &mut carry);
+            }
+            let mut p = i + k;
+            while carry != 0 {
+                t[p] = adc(t[p], 0, &mut carry);
+                p += 1;
+            }
+        }
+
+        // `result` is k+1 digits wide: whenever `n`'s top bit is set (true of
+        // any "real" modulus, e.g. RSA-style ones), the reduced value can
+        // land in `[n, 2n)`, which needs one more bit than `n`'s own k-digit
+        // width to represent before the conditional subtract below.
+        let mut result = t.split_off(k);
+
+        if result[k] != 0 || cmp_slice(&result[..k], n) != Less {
+            let mut borrow = 0;
+            for (ri, &ni) in result[..k].iter_mut().zip(n.iter()) {
+                *ri = sbb(*ri, ni, &mut borrow);
+            }
+            result[k] = sbb(result[k], 0, &mut borrow);
+        }
+
+        result.truncate(k);
+        FixedBigUint::from_digits(result)
+    }
+
+    /// Montgomery product `a * b * R^-1 mod n`, where `a` and `b` are
+    /// themselves already in Montgomery form.
+    fn mul(&self, a: &FixedBigUint<B>, b: &FixedBigUint<B>) -> FixedBigUint<B> {
+        self.redc(wide_mul(a.digits(), b.digits()))
+    }
+
+    /// Converts `x` (with `x < n`) into Montgomery form `x * R mod n` by
+    /// doubling-and-reducing `x` through all `BITS*k` bits of `R`: each step
+    /// doubles the running value and subtracts `n` once if that overflowed
+    /// past it, which is exactly what's needed since the value never exceeds
+    /// `2*n` between steps.
+    fn to_montgomery(&self, x: &FixedBigUint<B>) -> FixedBigUint<B> {
+        let k = self.digit_len;
+        let n = self.modulus.digits();
+        let mut acc = x.digits().to_vec();
+
+        for _ in 0..(big_digit::BITS * k) {
+            let mut carry = 0;
+            for ai in acc.iter_mut() {
+                *ai = adc(*ai, *ai, &mut carry);
+            }
+            if carry != 0 || cmp_slice(&acc, n) != Less {
+                let mut borrow = 0;
+                for (ai, &ni) in acc.iter_mut().zip(n.iter()) {
+                    *ai = sbb(*ai, ni, &mut borrow);
+                }
+            }
+        }
+
+        FixedBigUint::from_digits(acc)
+    }
+
+    /// `1` in Montgomery form, i.e. `R mod n`.
+    fn one(&self) -> FixedBigUint<B> {
+        let mut one = vec![0 as BigDigit; self.digit_len];
+        one[0] = 1;
+        self.to_montgomery(&FixedBigUint::from_digits(one))
+    }
+}
+
+/// Iterates over the bits of `x`'s digits, most significant first.
+fn bits_msb_first<B: BitLength>(x: &FixedBigUint<B>) -> Vec<bool> {
+    let mut bits = Vec::with_capacity(big_digit::BITS * x.digit_len());
+
+    for &digit in x.digits().iter().rev() {
+        for i in (0..big_digit::BITS).rev() {
+            bits.push((digit >> i) & 1 == 1);
+        }
+    }
+
+    bits
+}
+
+impl<B> ModPow for FixedBigUint<B> where B: BitLength {
+    type IntType = FixedBigUint<B>;
+
+    fn mod_pow(base: FixedBigUint<B>, exponent: FixedBigUint<B>, modulus: FixedBigUint<B>) -> FixedBigUint<B> {
+        assert!(base < modulus, "ModPow::mod_pow requires base < modulus");
+
+        let montgomery = Montgomery::new(&modulus);
+        let base_mont = montgomery.to_montgomery(&base);
+        let mut result = montgomery.one();
+
+        for bit in bits_msb_first(&exponent) {
+            result = montgomery.mul(&result, &result);
+            if bit {
+                result = montgomery.mul(&result, &base_mont);
+            }
+        }
+
+        // Undo the Montgomery form by REDC-ing once more: REDC(result) =
+        // result * R^-1 mod n = (actual_result * R) * R^-1 mod n.
+        montgomery.redc(result.digits().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixed_sizes::Bits128;
+
+    fn mk(s: &str) -> FixedBigUint<Bits128> {
+        FixedBigUint::from_str_radix(s, 10).unwrap()
+    }
+
+    #[test]
+    fn mod_pow_small() {
+        // 3^5 mod 7 = 243 mod 7 = 5
+        let result = FixedBigUint::mod_pow(mk("3"), mk("5"), mk("7"));
+        assert_eq!(result, mk("5"));
+    }
+
+    #[test]
+    fn mod_pow_top_bit_set_modulus() {
+        // Whenever the modulus's top bit is set, REDC's reduced value can
+        // land in [n, 2n), which only the k+1-digit `result` in `redc` can
+        // tell apart from a value already below `n` -- this is the exact
+        // case a prior REDC truncation bug got wrong.
+        let base = mk("123456789012345678901234567890123456789");
+        let exponent = mk("98765432109876543210987654321098765");
+        let modulus = mk("340282366920938463463374607431768211453");
+
+        let result = FixedBigUint::mod_pow(base, exponent, modulus);
+        assert_eq!(result, mk("244426292440966566558481828699128355315"));
+    }
+}
@@ -0,0 +1,339 @@
+use std::cmp;
+use std::fmt;
+
+use fixed_bigint::{FixedBigUint, BigDigit, DoubleBigDigit, big_digit, ones_mask};
+use fixed_sizes::{BitLength, DigitStorage};
+use num::traits::{Num, Zero, CheckedAdd, CheckedMul};
+
+/// Error returned when a string can't be parsed as a `FixedBigUint`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFixedBigUintError {
+    /// The input string was empty.
+    Empty,
+    /// A character wasn't a valid digit for the given radix.
+    InvalidDigit,
+    /// The parsed value doesn't fit in `2^B::bit_len()`.
+    Overflow,
+}
+
+impl fmt::Display for ParseFixedBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            ParseFixedBigUintError::Empty => "cannot parse integer from empty string",
+            ParseFixedBigUintError::InvalidDigit => "invalid digit found in string",
+            ParseFixedBigUintError::Overflow => "number too large for the fixed width",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl ::std::error::Error for ParseFixedBigUintError {}
+
+/// Largest `(chunk_len, radix^chunk_len)` with the power fitting in a single
+/// `BigDigit`, so non-power-of-two radices can be parsed/formatted several
+/// characters at a time instead of one at a time.
+fn biggest_power(radix: u32) -> (usize, BigDigit) {
+    let mut chunk_len = 1;
+    let mut chunk_pow = radix as DoubleBigDigit;
+
+    while chunk_pow * (radix as DoubleBigDigit) <= (BigDigit::max_value() as DoubleBigDigit) + 1 {
+        chunk_pow *= radix as DoubleBigDigit;
+        chunk_len += 1;
+    }
+
+    (chunk_len, chunk_pow as BigDigit)
+}
+
+fn pow_u32(base: u32, exp: usize) -> BigDigit {
+    let base = base as BigDigit;
+    let mut result: BigDigit = 1;
+
+    for _ in 0..exp {
+        result *= base;
+    }
+
+    result
+}
+
+/// Parses up to `chunk_len` characters (as ensured by `biggest_power`) as a
+/// single `BigDigit` in the given radix.
+fn parse_chunk(s: &str, radix: u32) -> Result<BigDigit, ParseFixedBigUintError> {
+    let mut value: BigDigit = 0;
+
+    for c in s.chars() {
+        let d = c.to_digit(radix).ok_or(ParseFixedBigUintError::InvalidDigit)?;
+        value = value * (radix as BigDigit) + (d as BigDigit);
+    }
+
+    Ok(value)
+}
+
+// `value % radix_digit` already fits in `u32` (it's below `radix <= 36`), but
+// the cast is only a no-op under the default 32-bit `BigDigit` -- it's load-
+// bearing once `u64_digit` is enabled.
+#[allow(clippy::unnecessary_cast)]
+fn chunk_to_str(mut value: BigDigit, radix: u32) -> String {
+    if value == 0 {
+        return "0".to_string();
+    }
+
+    let radix_digit = radix as BigDigit;
+    let mut chars = Vec::new();
+    while value != 0 {
+        chars.push(char::from_digit((value % radix_digit) as u32, radix).unwrap());
+        value /= radix_digit;
+    }
+
+    chars.iter().rev().collect()
+}
+
+/// Parses a power-of-two radix by packing `bits_per_char`-sized groups
+/// directly into the digit array, rather than going through multiply/add.
+fn from_str_radix_pow2<B: BitLength>(src: &str, radix: u32) -> Result<FixedBigUint<B>, ParseFixedBigUintError> {
+    let bits_per_char = radix.trailing_zeros() as usize;
+    let digit_len = B::Storage::zeroed().as_ref().len();
+    let total_bits = src.len() * bits_per_char;
+    let needed_words = (total_bits + big_digit::BITS - 1) / big_digit::BITS + 1;
+    let mut digits = vec![0 as BigDigit; cmp::max(needed_words, digit_len)];
+
+    let mut bit_pos = 0;
+    for c in src.chars().rev() {
+        let d = c.to_digit(radix).ok_or(ParseFixedBigUintError::InvalidDigit)? as BigDigit;
+        let word = bit_pos / big_digit::BITS;
+        let shift = bit_pos % big_digit::BITS;
+
+        digits[word] |= d << shift;
+        if shift + bits_per_char > big_digit::BITS {
+            digits[word + 1] |= d >> (big_digit::BITS - shift);
+        }
+
+        bit_pos += bits_per_char;
+    }
+
+    if digits[digit_len..].iter().any(|&w| w != 0) {
+        return Err(ParseFixedBigUintError::Overflow);
+    }
+
+    let top_mask = ones_mask((B::bit_len() % big_digit::BITS) as BigDigit);
+    if digits[digit_len - 1] & !top_mask != 0 {
+        return Err(ParseFixedBigUintError::Overflow);
+    }
+
+    digits.truncate(digit_len);
+
+    Ok(FixedBigUint::from_digits(digits))
+}
+
+impl<B> FixedBigUint<B> where B: BitLength {
+    /// Parses a `FixedBigUint` from its base-`radix` representation
+    /// (`radix` in `2..=36`). Rejects empty input, digits invalid for
+    /// `radix`, and any value too large for `2^B::bit_len()` -- it never
+    /// silently truncates.
+    pub fn from_str_radix(src: &str, radix: u32) -> Result<FixedBigUint<B>, ParseFixedBigUintError> {
+        assert!(radix >= 2 && radix <= 36, "radix must be in the range 2..=36");
+
+        if src.is_empty() {
+            return Err(ParseFixedBigUintError::Empty);
+        }
+
+        if radix.is_power_of_two() {
+            return from_str_radix_pow2::<B>(src, radix);
+        }
+
+        // Every valid digit is ASCII; checking up front also keeps the
+        // byte-offset chunk slicing below on character boundaries.
+        if !src.is_ascii() {
+            return Err(ParseFixedBigUintError::InvalidDigit);
+        }
+
+        let (chunk_len, _) = biggest_power(radix);
+        let src_len = src.len();
+        let first_len = match src_len % chunk_len {
+            0 => chunk_len,
+            n => n,
+        };
+
+        let mut result = FixedBigUint::<B>::zero();
+        let mut pos = 0;
+        let mut len = first_len;
+
+        while pos < src_len {
+            let value = parse_chunk(&src[pos..pos + len], radix)?;
+            let base = pow_u32(radix, len);
+
+            result = result.checked_mul(&FixedBigUint::from_digits(vec![base]))
+                .ok_or(ParseFixedBigUintError::Overflow)?
+                .checked_add(&FixedBigUint::from_digits(vec![value]))
+                .ok_or(ParseFixedBigUintError::Overflow)?;
+
+            pos += len;
+            len = chunk_len;
+        }
+
+        Ok(result)
+    }
+
+    /// Formats this value in base `radix` (`2..=36`), using lowercase
+    /// letters for digits above 9.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        assert!(radix >= 2 && radix <= 36, "radix must be in the range 2..=36");
+
+        if self.is_zero() {
+            return "0".to_string();
+        }
+
+        if radix.is_power_of_two() {
+            return self.to_str_radix_pow2(radix);
+        }
+
+        let (chunk_len, chunk_base) = biggest_power(radix);
+        let divisor = FixedBigUint::<B>::from_digits(vec![chunk_base]);
+
+        let mut chunks = Vec::new();
+        let mut value = self.clone();
+        while !value.is_zero() {
+            let (q, r) = value.div_rem(&divisor);
+            chunks.push(r.digits()[0]);
+            value = q;
+        }
+
+        let mut out = String::new();
+        for (i, &chunk) in chunks.iter().rev().enumerate() {
+            let digits = chunk_to_str(chunk, radix);
+
+            if i > 0 {
+                for _ in 0..(chunk_len - digits.len()) {
+                    out.push('0');
+                }
+            }
+
+            out.push_str(&digits);
+        }
+
+        out
+    }
+
+    /// Formats a power-of-two radix by unpacking `bits_per_char`-sized
+    /// groups directly from the digit array, rather than going through
+    /// repeated division.
+    fn to_str_radix_pow2(&self, radix: u32) -> String {
+        let bits_per_char = radix.trailing_zeros() as usize;
+        let char_mask = ones_mask(bits_per_char as BigDigit);
+        let digits = self.digits();
+
+        let mut chars = Vec::new();
+        let mut bit_pos = 0;
+        while bit_pos < B::bit_len() {
+            let word = bit_pos / big_digit::BITS;
+            let shift = bit_pos % big_digit::BITS;
+
+            let mut d = (digits[word] >> shift) & char_mask;
+            if shift + bits_per_char > big_digit::BITS && word + 1 < digits.len() {
+                d |= (digits[word + 1] << (big_digit::BITS - shift)) & char_mask;
+            }
+
+            chars.push(char::from_digit(d as u32, radix).unwrap());
+            bit_pos += bits_per_char;
+        }
+
+        while chars.len() > 1 && chars.last() == Some(&'0') {
+            chars.pop();
+        }
+
+        chars.iter().rev().collect()
+    }
+}
+
+impl<B> Num for FixedBigUint<B> where B: BitLength {
+    type FromStrRadixErr = ParseFixedBigUintError;
+
+    fn from_str_radix(src: &str, radix: u32) -> Result<FixedBigUint<B>, ParseFixedBigUintError> {
+        FixedBigUint::from_str_radix(src, radix)
+    }
+}
+
+impl<B> fmt::Display for FixedBigUint<B> where B: BitLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_str_radix(10))
+    }
+}
+
+impl<B> fmt::LowerHex for FixedBigUint<B> where B: BitLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_str_radix(16))
+    }
+}
+
+impl<B> fmt::UpperHex for FixedBigUint<B> where B: BitLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_str_radix(16).to_ascii_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixed_sizes::Bits128;
+
+    #[test]
+    fn decimal_round_trip() {
+        let n = FixedBigUint::<Bits128>::from_str_radix("340282366920938463463374607431768211453", 10).unwrap();
+        assert_eq!(n.to_str_radix(10), "340282366920938463463374607431768211453");
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        // Hex is a power-of-two radix, so this exercises the bit-packing
+        // from_str_radix_pow2/to_str_radix_pow2 path instead of the
+        // multiply/divide one decimal goes through.
+        let n = FixedBigUint::<Bits128>::from_str_radix("ff0123456789abcdef", 16).unwrap();
+        assert_eq!(n.to_str_radix(16), "ff0123456789abcdef");
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let n = FixedBigUint::<Bits128>::from_str_radix("101101", 2).unwrap();
+        assert_eq!(n.to_str_radix(2), "101101");
+        assert_eq!(n.to_str_radix(10), "45");
+    }
+
+    #[test]
+    fn non_power_of_two_radix_chunking() {
+        // `radix = 7` isn't a power of two, so parsing/formatting goes
+        // through biggest_power's multi-character chunking instead of
+        // parsing/emitting one digit at a time.
+        let n = FixedBigUint::<Bits128>::from_str_radix("123456123456123456", 7).unwrap();
+        assert_eq!(n.to_str_radix(7), "123456123456123456");
+    }
+
+    #[test]
+    fn zero_and_empty() {
+        assert_eq!(FixedBigUint::<Bits128>::from_str_radix("0", 10).unwrap().to_str_radix(10), "0");
+        assert_eq!(
+            FixedBigUint::<Bits128>::from_str_radix("", 10),
+            Err(ParseFixedBigUintError::Empty)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_digit() {
+        assert_eq!(
+            FixedBigUint::<Bits128>::from_str_radix("12a", 10),
+            Err(ParseFixedBigUintError::InvalidDigit)
+        );
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        // One past Bits128::max_value().
+        assert_eq!(
+            FixedBigUint::<Bits128>::from_str_radix("340282366920938463463374607431768211456", 10),
+            Err(ParseFixedBigUintError::Overflow)
+        );
+        assert_eq!(
+            FixedBigUint::<Bits128>::from_str_radix("100000000000000000000000000000000", 16),
+            Err(ParseFixedBigUintError::Overflow)
+        );
+    }
+}